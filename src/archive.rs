@@ -0,0 +1,52 @@
+//! Opt-in, zero-copy serialization of an interned term DAG with structural
+//! sharing preserved, via `rkyv`.
+//!
+//! Gated behind the `archive` feature: a whole [`crate::unsync::Table`] can
+//! be flattened to bytes from a root set and later reloaded, re-interning
+//! every node so that loaded terms compare `==` to any structurally
+//! identical term already present in the live table. Useful for caching
+//! elaborated/optimized term graphs to disk between runs.
+
+use crate::{Id, Trace};
+
+/// Describes how a consed type flattens into an archivable node whose
+/// children are dense `u32` indices into the serialized node array rather
+/// than pointers, and how to rebuild it from already-reinterned children.
+///
+/// Analogous to [`Trace`], but for the apply direction: `from_node` is given
+/// back the children `to_node` described by `Id`.
+pub trait Dag: Trace + Sized {
+    /// Plain, on-disk shape of one node: the same data as `Self`, but with
+    /// every child reference replaced by its dense index in the serialized
+    /// node array.
+    type Node: rkyv::Archive + rkyv::Serialize<rkyv::ser::serializers::AllocSerializer<1024>>;
+
+    /// The re-interned, already-`==`-deduplicated handle a loaded child is
+    /// resolved to, e.g. `Hc<Self, I>` for whichever table `Self` is
+    /// deserialized into.
+    type Handle: Clone;
+
+    /// Flatten `self` into a [`Self::Node`], looking up each child's dense
+    /// index via `index_of`.
+    fn to_node(&self, index_of: &mut dyn FnMut(Id) -> u32) -> Self::Node;
+
+    /// Enumerate the dense child indices embedded in an archived node, in
+    /// the same order [`Self::from_node`]'s `children` expects them.
+    ///
+    /// Analogous to [`Trace::children`], but walking an archived node's
+    /// indices instead of a live value's `Id`s.
+    fn child_indices(node: &<Self::Node as rkyv::Archive>::Archived, visit: &mut dyn FnMut(u32));
+
+    /// Rebuild a `Self` from a decoded node and its already-reinterned
+    /// children, resolved via [`Self::child_indices`] to the matching
+    /// [`Self::Handle`] for each one.
+    fn from_node(node: &<Self::Node as rkyv::Archive>::Archived, children: &[Self::Handle]) -> Self;
+}
+
+/// Worklist entry for the iterative, double-pushed post-order DFS that
+/// [`crate::unsync::Table::serialize_dag`] uses to topologically order
+/// nodes so that every child is emitted before its parent.
+pub(crate) enum Frame {
+    Enter(Id),
+    Exit(Id),
+}
@@ -1,13 +1,42 @@
 //! A cache from terms that does not retain its keys.
 
 use fxhash::FxHashMap as HashMap;
+use std::collections::hash_map;
 
 use crate::{HasTable, Weak, WeakOf};
 
+/// Number of mutating operations (`entry`, `get_or_insert_with`) between
+/// opportunistic [`CacheOf::collect`] passes. See
+/// [`CacheOf::set_collect_interval`] to change it per-cache.
+const DEFAULT_COLLECT_INTERVAL: usize = 128;
+
 /// A cache from terms that does not retain its keys.
-#[derive(Clone, Default)]
+///
+/// Dead weak keys are swept out opportunistically instead of only when the
+/// caller remembers to call [`collect`](CacheOf::collect): every mutating
+/// operation bumps an internal counter, and once it crosses
+/// `collect_interval` a single `retain` pass runs and the counter resets,
+/// amortizing cleanup to O(1) per op.
 pub struct CacheOf<D: HasTable, V> {
     inner: HashMap<WeakOf<D>, V>,
+    ops_since_collect: usize,
+    collect_interval: usize,
+}
+
+impl<D: HasTable, V: Clone> Clone for CacheOf<D, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            ops_since_collect: self.ops_since_collect,
+            collect_interval: self.collect_interval,
+        }
+    }
+}
+
+impl<D: HasTable, V> Default for CacheOf<D, V> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<D: HasTable, V> CacheOf<D, V> {
@@ -15,17 +44,55 @@ impl<D: HasTable, V> CacheOf<D, V> {
     pub fn new() -> Self {
         Self {
             inner: HashMap::default(),
+            ops_since_collect: 0,
+            collect_interval: DEFAULT_COLLECT_INTERVAL,
         }
     }
     /// Create an empty cache with room for `n` items before allocation.
     pub fn with_capacity(n: usize) -> Self {
         Self {
             inner: HashMap::with_capacity_and_hasher(n, fxhash::FxBuildHasher::default()),
+            ops_since_collect: 0,
+            collect_interval: DEFAULT_COLLECT_INTERVAL,
         }
     }
+
+    /// Change how many mutating operations are allowed between opportunistic
+    /// `collect` passes. Lower values keep the table smaller at the cost of
+    /// more frequent sweeps; higher values amortize the sweep cost further.
+    pub fn set_collect_interval(&mut self, interval: usize) {
+        self.collect_interval = interval;
+    }
+
     /// Remove entries with free'd keys.
     pub fn collect(&mut self) {
         self.inner.retain(|k, _| k.upgrade().is_some());
+        self.ops_since_collect = 0;
+    }
+
+    fn bump(&mut self) {
+        self.ops_since_collect += 1;
+        if self.ops_since_collect >= self.collect_interval {
+            self.collect();
+        }
+    }
+
+    /// Entry-style access to the cache, keyed by `key`'s interned identity.
+    /// Counts as a mutating operation towards the next opportunistic
+    /// [`collect`](CacheOf::collect).
+    pub fn entry(&mut self, key: &D) -> Entry<'_, D, V> {
+        self.bump();
+        match self.inner.entry(key.downgrade()) {
+            hash_map::Entry::Occupied(e) => Entry::Occupied(e),
+            hash_map::Entry::Vacant(e) => Entry::Vacant(e),
+        }
+    }
+
+    /// Get the cached value for `key`, computing and inserting it via `f` if
+    /// absent. The key is stored as a [`WeakOf<D>`] so the cache does not
+    /// keep `key` alive.
+    pub fn get_or_insert_with(&mut self, key: &D, f: impl FnOnce() -> V) -> &mut V {
+        self.entry(key).or_insert_with(f)
     }
 }
 
@@ -42,3 +109,26 @@ impl<D: HasTable, V> std::ops::DerefMut for CacheOf<D, V> {
         &mut self.inner
     }
 }
+
+/// A view into a single entry of a [`CacheOf`], obtained via
+/// [`CacheOf::entry`]. Mirrors [`std::collections::hash_map::Entry`], but
+/// keyed by a term's interned identity rather than its value.
+pub enum Entry<'a, D: HasTable, V> {
+    Occupied(hash_map::OccupiedEntry<'a, WeakOf<D>, V>),
+    Vacant(hash_map::VacantEntry<'a, WeakOf<D>, V>),
+}
+
+impl<'a, D: HasTable, V> Entry<'a, D, V> {
+    /// Get the entry's value, computing and inserting it via `f` if vacant.
+    pub fn or_insert_with(self, f: impl FnOnce() -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(f()),
+        }
+    }
+
+    /// Get the entry's value, inserting `default` if vacant.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+}
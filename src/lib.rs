@@ -1,14 +1,38 @@
-//pub mod cache;
+pub mod cache;
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod sync;
 pub mod unsync;
 pub use fxhash;
 
 use std::cmp::{Eq, Ord, PartialEq, PartialOrd};
 
-pub trait HasTable: Sized {
+/// Implemented by the zero-sized key type (e.g. the `LocalKey`/`GlobalKey`
+/// generated by `generate_hashcons_{unsync,sync}!`) that identifies a
+/// concrete consed type's table, so `Hc<T, I>` can reach it generically.
+pub trait TableKey: Sized + 'static {
     type Table;
     fn table() -> &'static Self::Table;
 }
 
+/// Implemented by a consed handle (e.g. `Hc<T, I>`) so it can be used as a
+/// [`crate::cache::CacheOf`] key without the cache needing to know about the
+/// underlying table machinery.
+pub trait HasTable: Sized {
+    /// The weak, non-owning handle produced by downgrading a `Self`, used as
+    /// a cache key that doesn't keep its subject alive.
+    type Weak: Weak<Self>;
+    fn downgrade(&self) -> Self::Weak;
+}
+
+/// A weak handle for a `D`, obtained via `HasTable::downgrade`.
+pub trait Weak<D>: Clone + Eq + std::hash::Hash {
+    fn upgrade(&self) -> Option<D>;
+}
+
+/// The weak handle type produced by downgrading a `D`.
+pub type WeakOf<D> = <D as HasTable>::Weak;
+
 //pub trait Table<T>
 //where T: HasTable<Table=Self>
 //{
@@ -47,6 +71,16 @@ pub trait HasTable: Sized {
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
 pub struct Id(pub u64);
 
+/// Enumerates the immediate children of a consed value by `Id`.
+///
+/// Implemented by the user for their consed type so that
+/// `Table::gc_from_roots` can walk the term DAG during bulk mark-and-sweep
+/// collection. The DAG is assumed acyclic and immutable, which is what
+/// guarantees the mark phase terminates.
+pub trait Trace {
+    fn children(&self, visit: &mut dyn FnMut(Id));
+}
+
 impl std::fmt::Display for Id {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "id{}", self.0)
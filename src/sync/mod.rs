@@ -0,0 +1,299 @@
+#![allow(dead_code)]
+
+//! Thread-safe counterpart to [`crate::unsync`], backed by `Arc` and a
+//! sharded table so that concurrent interning of distinct subterms proceeds
+//! without contending on a single global lock.
+
+mod table;
+use table::Table;
+
+use crate::Id;
+use std::fmt;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::ops::Deref;
+use std::sync::Arc;
+
+// Trait alias
+pub trait Consable: Clone + Debug + Eq + Hash + Send + Sync + 'static {}
+impl<T> Consable for T where T: Clone + Debug + Eq + Hash + Send + Sync + 'static {}
+
+// Trait alias
+pub trait TableKey<T: Consable>: crate::TableKey<Table = Table<T, Self>> {}
+impl<T: Consable, I> TableKey<T> for I where I: crate::TableKey<Table = Table<T, Self>> {}
+
+pub struct Hc<T: Consable, I: TableKey<T>> {
+    data: Arc<T>,
+    _marker: std::marker::PhantomData<I>,
+}
+
+impl<T: Consable, I: TableKey<T>> Hc<T, I> {
+    pub fn new(t: T) -> Self {
+        <I as crate::TableKey>::Table::create(t)
+    }
+
+    fn new_unchecked(data: T) -> Self {
+        Hc {
+            data: Arc::new(data),
+            _marker: std::marker::PhantomData,
+        }
+    }
+    pub fn id(this: &Hc<T, I>) -> Id {
+        Id(Arc::as_ptr(&this.data).addr() as u64)
+    }
+
+    pub fn downgrade(this: &Hc<T, I>) -> Weak<T, I> {
+        Weak {
+            data: Arc::downgrade(&this.data),
+            _marker: std::marker::PhantomData,
+        }
+    }
+    pub fn strong_count(this: &Self) -> usize {
+        Arc::strong_count(&this.data)
+    }
+
+    pub fn weak_count(this: &Self) -> usize {
+        Arc::weak_count(&this.data)
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> Drop for Hc<T, I> {
+    fn drop(&mut self) {
+        // This and the table entry
+        if Arc::strong_count(&self.data) == 2 && !std::thread::panicking() {
+            <I as crate::TableKey>::Table::add_to_gc(Hc::downgrade(self));
+        }
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> Debug for Hc<T, I> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Hc")
+            .field("id", &Hc::id(self))
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> Deref for Hc<T, I> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> Clone for Hc<T, I> {
+    fn clone(&self) -> Self {
+        Hc {
+            data: self.data.clone(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> PartialEq for Hc<T, I> {
+    fn eq(&self, other: &Self) -> bool {
+        Hc::id(self) == Hc::id(other)
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> Eq for Hc<T, I> {}
+
+impl<T: Consable, I: TableKey<T>> Hash for Hc<T, I> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hc::id(self).hash(state)
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> crate::HasTable for Hc<T, I> {
+    type Weak = Weak<T, I>;
+    fn downgrade(&self) -> Self::Weak {
+        Hc::downgrade(self)
+    }
+}
+
+pub struct Weak<T: Consable, I: TableKey<T>> {
+    data: std::sync::Weak<T>,
+    _marker: std::marker::PhantomData<I>,
+}
+
+impl<T: Consable, I: TableKey<T>> Clone for Weak<T, I> {
+    fn clone(&self) -> Self {
+        Weak {
+            data: self.data.clone(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> Debug for Weak<T, I> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Weak")
+            .field("id", &self.id())
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> Weak<T, I> {
+    pub fn id(&self) -> Id {
+        Id(self.data.as_ptr().addr() as u64)
+    }
+
+    pub fn upgrade(&self) -> Option<Hc<T, I>> {
+        self.data.upgrade().map(|data| Hc {
+            data,
+            _marker: std::marker::PhantomData,
+        })
+    }
+
+    pub fn weak_count(this: &Self) -> usize {
+        this.data.weak_count()
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> PartialEq for Weak<T, I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> Eq for Weak<T, I> {}
+
+impl<T: Consable, I: TableKey<T>> Hash for Weak<T, I> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state)
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> crate::Weak<Hc<T, I>> for Weak<T, I> {
+    fn upgrade(&self) -> Option<Hc<T, I>> {
+        Weak::upgrade(self)
+    }
+}
+
+#[macro_export]
+macro_rules! generate_hashcons_sync {
+    (mod $mod:ident, $ty:ident) => {
+        mod $mod {
+            mod inner {
+                pub enum GlobalKey {}
+
+                static HC_TABLE: std::sync::OnceLock<
+                    $crate::sync::Table<super::super::$ty, GlobalKey>,
+                > = std::sync::OnceLock::new();
+
+                impl $crate::TableKey for GlobalKey {
+                    type Table = $crate::sync::Table<super::super::$ty, GlobalKey>;
+                    fn table() -> &'static Self::Table {
+                        HC_TABLE.get_or_init($crate::sync::Table::new)
+                    }
+                }
+            }
+
+            pub type Hc = $crate::sync::Hc<super::$ty, inner::GlobalKey>;
+            pub type Table = $crate::sync::Table<super::$ty, inner::GlobalKey>;
+            pub type Weak = $crate::sync::Weak<super::$ty, inner::GlobalKey>;
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #[derive(Debug, Clone, Eq, Hash, PartialEq)]
+    pub enum LangInner {
+        Val(i32),
+        Add(Lang, Lang),
+    }
+    generate_hashcons_sync!(mod test1, LangInner);
+    use test1::Hc as Lang;
+
+    #[test]
+    fn dedups_across_threads() {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    Lang::new(LangInner::Add(
+                        Lang::new(LangInner::Val(12)),
+                        Lang::new(LangInner::Val(13)),
+                    ))
+                })
+            })
+            .collect();
+
+        let terms: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(terms.windows(2).all(|w| w[0] == w[1]));
+        assert_eq!(test1::Table::len(), 3);
+    }
+
+    #[test]
+    fn gc_reclaims_dead_entries() {
+        let add = Lang::new(LangInner::Add(
+            Lang::new(LangInner::Val(20)),
+            Lang::new(LangInner::Val(21)),
+        ));
+        assert_eq!(test1::Table::len(), 3);
+        drop(add);
+        test1::Table::gc();
+        assert_eq!(test1::Table::len(), 0);
+    }
+
+    // Kept in its own table (rather than reusing `test1::Lang`) so these
+    // don't race with the exact-`len()` assertions the tests above make on
+    // the same process-wide static table.
+    #[cfg(feature = "rayon")]
+    mod par {
+        #[derive(Debug, Clone, Eq, Hash, PartialEq)]
+        pub enum NumInner {
+            Val(i32),
+            Add(Num, Num),
+        }
+        generate_hashcons_sync!(mod test2, NumInner);
+        use test2::Hc as Num;
+
+        impl crate::Trace for NumInner {
+            fn children(&self, visit: &mut dyn FnMut(crate::Id)) {
+                if let NumInner::Add(l, r) = self {
+                    visit(Num::id(l));
+                    visit(Num::id(r));
+                }
+            }
+        }
+
+        #[test]
+        fn par_for_each_visits_every_interned_node() {
+            use std::sync::atomic::{AtomicUsize, Ordering};
+
+            let _add = Num::new(NumInner::Add(
+                Num::new(NumInner::Val(1)),
+                Num::new(NumInner::Val(2)),
+            ));
+
+            let count = AtomicUsize::new(0);
+            test2::Table::par_for_each(|_: &NumInner| {
+                count.fetch_add(1, Ordering::Relaxed);
+            });
+            assert_eq!(count.load(Ordering::Relaxed), test2::Table::len());
+        }
+
+        #[test]
+        fn par_gc_from_roots_matches_sequential_semantics() {
+            let root = Num::new(NumInner::Add(
+                Num::new(NumInner::Val(10)),
+                Num::new(NumInner::Val(11)),
+            ));
+            let garbage = Num::new(NumInner::Val(12));
+            assert_eq!(test2::Table::len(), 4);
+
+            // `garbage` is still held externally, so the parallel sweep
+            // must skip it too, same as the sequential `gc_from_roots`.
+            assert_eq!(test2::Table::par_gc_from_roots(std::iter::once(&root)), 0);
+            assert_eq!(test2::Table::len(), 4);
+
+            drop(garbage);
+            assert_eq!(test2::Table::par_gc_from_roots(std::iter::once(&root)), 1);
+            assert_eq!(test2::Table::len(), 3);
+        }
+    }
+}
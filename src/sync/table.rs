@@ -0,0 +1,285 @@
+#![allow(dead_code)]
+
+use crate::Id;
+use crate::Trace;
+use crate::sync::{Consable, Hc, Weak};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+use crate::fxhash::FxHashMap as HashMap;
+use crate::fxhash::FxHashSet as HashSet;
+use crate::sync::TableKey;
+
+/// Number of shards a `Table` is partitioned into. Each shard has its own
+/// lock, so interning two subterms that land in different shards never
+/// contends. Sized as a compromise between per-shard contention and the
+/// fixed cost of sweeping every shard during `len`/`for_each`/`gc`.
+const NUM_SHARDS: usize = 32;
+const SHARD_BITS: u32 = NUM_SHARDS.trailing_zeros();
+
+fn shard_index(hash: u64) -> usize {
+    // Top bits, so that the low bits hashbrown-style maps still use for
+    // in-shard probing stay independent of shard placement.
+    (hash >> (u64::BITS - SHARD_BITS)) as usize
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = crate::fxhash::FxHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub struct Table<T: Consable, I: TableKey<T>> {
+    shards: [InnerTable<T, I>; NUM_SHARDS],
+    gc: Mutex<GcData<T, I>>,
+}
+
+impl<T: Consable, I: TableKey<T>> Table<T, I> {
+    /// Build a fresh, empty table. Unlike `unsync::Table`, a `sync::Table` is
+    /// not required to live in a single `static`, so there is no unsafe
+    /// `new_unchecked` constructor here: `generate_hashcons_sync!` stashes
+    /// the single shared instance behind a `OnceLock` instead.
+    pub fn new() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| InnerTable::default()),
+            gc: Mutex::new(GcData::default()),
+        }
+    }
+
+    fn shard(&self, hash: u64) -> &InnerTable<T, I> {
+        &self.shards[shard_index(hash)]
+    }
+
+    pub fn gc() -> usize {
+        <I as crate::TableKey>::table().gc_impl()
+    }
+
+    pub fn len() -> usize {
+        <I as crate::TableKey>::table()
+            .shards
+            .iter()
+            .map(|shard| shard.table.lock().unwrap().len())
+            .sum()
+    }
+
+    pub fn for_each<F: FnMut(&T)>(mut f: F) {
+        for shard in &<I as crate::TableKey>::table().shards {
+            shard.table.lock().unwrap().keys().for_each(&mut f);
+        }
+    }
+
+    /// Run `f` over every interned node in parallel, one rayon task per
+    /// shard. Shards are independently locked, so this naturally
+    /// parallelizes without any extra bookkeeping.
+    #[cfg(feature = "rayon")]
+    pub fn par_for_each<F>(f: F)
+    where
+        F: Fn(&T) + Sync,
+        I: Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        <I as crate::TableKey>::table()
+            .shards
+            .par_iter()
+            .for_each(|shard| shard.table.lock().unwrap().values().for_each(|hc| f(hc)));
+    }
+
+    pub(crate) fn create(t: T) -> Hc<T, I> {
+        let table = <I as crate::TableKey>::table();
+        let hash = hash_of(&t);
+        table.shard(hash).create(t)
+    }
+
+    pub(crate) fn add_to_gc(w: Weak<T, I>) {
+        let table = <I as crate::TableKey>::table();
+        table
+            .gc
+            .lock()
+            .unwrap_or_else(|_| panic!("Failed to add to gc queue"))
+            .to_collect
+            .push(w);
+    }
+
+    pub fn reserve(num_nodes: usize) {
+        let table = <I as crate::TableKey>::table();
+        let per_shard = num_nodes.div_ceil(NUM_SHARDS);
+        for shard in &table.shards {
+            shard.table.lock().unwrap().reserve(per_shard);
+        }
+    }
+
+    /// Bulk-collect everything unreachable from `roots`, across all shards.
+    ///
+    /// See `unsync::Table::gc_from_roots` for the mark-and-sweep algorithm;
+    /// the only difference here is that the by-`Id` index used for marking
+    /// is built by locking each shard in turn, and the sweep likewise runs
+    /// shard by shard.
+    pub fn gc_from_roots<'a>(roots: impl IntoIterator<Item = &'a Hc<T, I>>) -> usize
+    where
+        T: Trace + 'a,
+    {
+        <I as crate::TableKey>::table().gc_from_roots_impl(roots.into_iter())
+    }
+
+    fn gc_from_roots_impl<'a>(&self, roots: impl Iterator<Item = &'a Hc<T, I>>) -> usize
+    where
+        T: Trace + 'a,
+    {
+        let mut marked: HashSet<Id> = HashSet::default();
+        let mut worklist: Vec<Id> = roots.map(Hc::id).collect();
+        {
+            let locked: Vec<_> = self.shards.iter().map(|s| s.table.lock().unwrap()).collect();
+            let by_id: HashMap<Id, &T> = locked
+                .iter()
+                .flat_map(|shard| shard.values().map(|hc| (Hc::id(hc), &**hc)))
+                .collect();
+            while let Some(id) = worklist.pop() {
+                if !marked.insert(id) {
+                    continue;
+                }
+                if let Some(data) = by_id.get(&id) {
+                    data.children(&mut |child| worklist.push(child));
+                }
+            }
+        }
+
+        let mut collected = 0;
+        for shard in &self.shards {
+            let mut table = shard.table.lock().unwrap();
+            let before = table.len();
+            table.retain(|_, hc| marked.contains(&Hc::id(hc)) || Hc::strong_count(hc) != 1);
+            collected += before - table.len();
+        }
+        collected
+    }
+
+    /// Same as [`Table::gc_from_roots`], but the sweep runs on all shards in
+    /// parallel via rayon instead of one at a time.
+    #[cfg(feature = "rayon")]
+    pub fn par_gc_from_roots<'a>(roots: impl IntoIterator<Item = &'a Hc<T, I>>) -> usize
+    where
+        T: Trace + Sync + 'a,
+        I: Send + Sync,
+    {
+        <I as crate::TableKey>::table().par_gc_from_roots_impl(roots.into_iter())
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_gc_from_roots_impl<'a>(&self, roots: impl Iterator<Item = &'a Hc<T, I>>) -> usize
+    where
+        T: Trace + Sync + 'a,
+        I: Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        let mut marked: HashSet<Id> = HashSet::default();
+        let mut worklist: Vec<Id> = roots.map(Hc::id).collect();
+        {
+            let locked: Vec<_> = self.shards.iter().map(|s| s.table.lock().unwrap()).collect();
+            let by_id: HashMap<Id, &T> = locked
+                .iter()
+                .flat_map(|shard| shard.values().map(|hc| (Hc::id(hc), &**hc)))
+                .collect();
+            while let Some(id) = worklist.pop() {
+                if !marked.insert(id) {
+                    continue;
+                }
+                if let Some(data) = by_id.get(&id) {
+                    data.children(&mut |child| worklist.push(child));
+                }
+            }
+        }
+
+        self.shards
+            .par_iter()
+            .map(|shard| {
+                let mut table = shard.table.lock().unwrap();
+                let before = table.len();
+                table.retain(|_, hc| marked.contains(&Hc::id(hc)) || Hc::strong_count(hc) != 1);
+                before - table.len()
+            })
+            .sum()
+    }
+
+    fn gc_impl(&self) -> usize {
+        if std::thread::panicking() {
+            return 0;
+        }
+
+        let mut collected = 0;
+        loop {
+            let Some(w) = ({ self.gc.lock().unwrap().to_collect.pop() }) else {
+                break;
+            };
+
+            if w.data.strong_count() != 1 {
+                continue;
+            }
+
+            let Some(rc) = w.data.upgrade() else {
+                continue;
+            };
+            let hash = hash_of(&*rc);
+            let mut table = self.shard(hash).table.lock().unwrap();
+
+            // Re-check under the shard lock: a concurrent `create` may have
+            // cloned this entry (resurrecting it) in the window between the
+            // strong_count check above and acquiring the lock. `rc` plus
+            // the table's own clone account for a strong count of 2; a
+            // higher count means someone else now holds it, so leave it
+            // interned.
+            if std::sync::Arc::strong_count(&rc) != 2 {
+                continue;
+            }
+
+            let hc = table.remove(&*rc).expect("missing from table");
+            drop(table);
+            // Need rc to drop before hc, otherwise hc's ref count will be 2 when it drops
+            // and it'll re-add all its children to the queue
+            drop(rc);
+            drop(hc);
+
+            collected += 1;
+        }
+        collected
+    }
+}
+
+struct GcData<T: Consable, I: TableKey<T>> {
+    to_collect: Vec<Weak<T, I>>,
+}
+
+impl<T: Consable, I: TableKey<T>> Default for GcData<T, I> {
+    fn default() -> Self {
+        Self {
+            to_collect: Default::default(),
+        }
+    }
+}
+
+pub struct InnerTable<T: Consable, I: TableKey<T>> {
+    table: Mutex<HashMap<T, Hc<T, I>>>,
+    _marker: PhantomData<I>,
+}
+
+impl<T: Consable, I: TableKey<T>> Default for InnerTable<T, I> {
+    fn default() -> Self {
+        Self {
+            table: Default::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Consable, I: TableKey<T>> InnerTable<T, I> {
+    fn create(&self, data: T) -> Hc<T, I> {
+        self.table
+            .lock()
+            .unwrap()
+            .entry(data)
+            .or_insert_with_key(|key| Hc::new_unchecked(key.clone()))
+            .clone()
+    }
+}
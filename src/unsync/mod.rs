@@ -107,6 +107,13 @@ impl<T: Consable, I: TableKey<T>> Hash for Hc<T, I> {
     }
 }
 
+impl<T: Consable, I: TableKey<T>> crate::HasTable for Hc<T, I> {
+    type Weak = Weak<T, I>;
+    fn downgrade(&self) -> Self::Weak {
+        Hc::downgrade(self)
+    }
+}
+
 pub struct Weak<T: Consable, I: TableKey<T>> {
     data: std::rc::Weak<T>,
     _marker: std::marker::PhantomData<I>,
@@ -161,6 +168,12 @@ impl<T: Consable, I: TableKey<T>> Hash for Weak<T, I> {
     }
 }
 
+impl<T: Consable, I: TableKey<T>> crate::Weak<Hc<T, I>> for Weak<T, I> {
+    fn upgrade(&self) -> Option<Hc<T, I>> {
+        Weak::upgrade(self)
+    }
+}
+
 #[macro_export]
 macro_rules! generate_hashcons_unsync {
     (mod $mod:ident, $ty:ident) => {
@@ -217,6 +230,30 @@ mod tests {
         eprintln!("TABLE LEN {}", test1::Table::gc());
     }
 
+    #[test]
+    fn cache_of_sweeps_dead_keys_opportunistically() {
+        use crate::cache::CacheOf;
+
+        let mut cache: CacheOf<Lang, i32> = CacheOf::new();
+        cache.set_collect_interval(2);
+
+        let key1 = Lang::new(LangInner::Val(100));
+        cache.get_or_insert_with(&key1, || 1);
+        drop(key1);
+        // Dropping `key1` only queues it for collection; the table still
+        // holds a strong `Hc` until `gc` actually evicts it, so force that
+        // now to make the cache's weak key dead.
+        test1::Table::gc();
+        assert_eq!(cache.len(), 1);
+
+        // This is the 2nd mutating op, crossing `collect_interval`: it
+        // should sweep the dead `key1` entry before inserting `key2`'s.
+        let key2 = Lang::new(LangInner::Val(101));
+        cache.get_or_insert_with(&key2, || 2);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(*cache.get_or_insert_with(&key2, || panic!("should be cached")), 2);
+    }
+
     // How we'd implement for circ
     mod circ {
         generate_hashcons_unsync!(mod inner, TermInner);
@@ -241,6 +278,7 @@ mod tests {
             }
         }
         #[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+        #[cfg_attr(feature = "archive", derive(rkyv::Archive, rkyv::Serialize))]
         pub enum Op {
             Add,
             Val(i32),
@@ -267,6 +305,128 @@ mod tests {
                 &self.cs
             }
         }
+        impl crate::Trace for TermInner {
+            fn children(&self, visit: &mut dyn FnMut(crate::Id)) {
+                for c in self.cs.iter() {
+                    visit(Hc::id(&c.0));
+                }
+            }
+        }
+
+        #[cfg(feature = "archive")]
+        #[derive(rkyv::Archive, rkyv::Serialize)]
+        pub struct TermNode {
+            op: Op,
+            children: Vec<u32>,
+        }
+
+        #[cfg(feature = "archive")]
+        impl crate::archive::Dag for TermInner {
+            type Node = TermNode;
+            type Handle = Hc;
+
+            fn to_node(&self, index_of: &mut dyn FnMut(crate::Id) -> u32) -> Self::Node {
+                TermNode {
+                    op: self.op,
+                    children: self.cs.iter().map(|c| index_of(Hc::id(&c.0))).collect(),
+                }
+            }
+
+            fn child_indices(
+                node: &<Self::Node as rkyv::Archive>::Archived,
+                visit: &mut dyn FnMut(u32),
+            ) {
+                for idx in node.children.iter() {
+                    visit(*idx);
+                }
+            }
+
+            fn from_node(node: &<Self::Node as rkyv::Archive>::Archived, children: &[Hc]) -> Self {
+                let op = match &node.op {
+                    ArchivedOp::Add => Op::Add,
+                    ArchivedOp::Val(v) => Op::Val(*v),
+                };
+                TermInner {
+                    op,
+                    cs: children.iter().cloned().map(Term).collect(),
+                }
+            }
+        }
+
+        #[cfg(feature = "archive")]
+        #[test]
+        fn archive_round_trip_preserves_sharing_and_dedup() {
+            let five = Term::new(Op::Val(5), vec![]);
+            let add = Term::new(Op::Add, vec![five.clone(), five.clone()]);
+
+            let bytes = TermTable::serialize_dag(std::iter::once(&add.0));
+            let loaded = TermTable::deserialize_dag(&bytes);
+
+            // One node for `five`, one for `add`: the shared child was only
+            // emitted (and reloaded) once.
+            assert_eq!(loaded.len(), 2);
+            let loaded_five = Term(loaded[0].clone());
+            let loaded_add = Term(loaded[1].clone());
+
+            // Both were already present in the table, so reloading
+            // re-interned them into those same entries rather than minting
+            // new ones.
+            assert_eq!(loaded_five, five);
+            assert_eq!(loaded_add, add);
+
+            // Structural sharing preserved: both children of the reloaded
+            // `add` resolve to the exact same interned node.
+            assert_eq!(loaded_add.cs()[0], loaded_add.cs()[1]);
+        }
+
+        #[test]
+        fn gc_from_roots_keeps_only_reachable() {
+            let root = Term::new(
+                Op::Add,
+                vec![Term::new(Op::Val(5), vec![]), Term::new(Op::Val(6), vec![])],
+            );
+            let garbage = Term::new(Op::Val(7), vec![]);
+            assert_eq!(TermTable::len(), 4);
+
+            // `garbage` is still held externally (`strong_count == 2`), so
+            // it must survive the sweep even though it wasn't passed in as
+            // a root.
+            assert_eq!(TermTable::gc_from_roots(std::iter::once(&root.0)), 0);
+            assert_eq!(TermTable::len(), 4);
+
+            // Once the external handle is gone it's table-only, so the next
+            // sweep reclaims it.
+            drop(garbage);
+            assert_eq!(TermTable::gc_from_roots(std::iter::once(&root.0)), 1);
+            assert_eq!(TermTable::len(), 3);
+        }
+    }
+
+    // `par_for_each` requires `T: Sync`, which `circ::TermInner` isn't
+    // (its children are `Rc`-backed `Hc`s); use a plain, non-recursive
+    // consed value instead.
+    #[cfg(feature = "rayon")]
+    mod par {
+        #[derive(Debug, Clone, Eq, Hash, PartialEq)]
+        pub struct Num(i32);
+        generate_hashcons_unsync!(mod inner, Num);
+        use inner::Hc as NumHc;
+        pub use inner::Table as NumTable;
+
+        #[test]
+        fn par_for_each_visits_every_interned_node() {
+            use std::sync::atomic::{AtomicUsize, Ordering};
+
+            let _a = NumHc::new(Num(1));
+            let _b = NumHc::new(Num(2));
+            let _c = NumHc::new(Num(3));
+
+            let count = AtomicUsize::new(0);
+            NumTable::par_for_each(|_: &Num| {
+                count.fetch_add(1, Ordering::Relaxed);
+            });
+            assert_eq!(count.load(Ordering::Relaxed), NumTable::len());
+        }
     }
 
     #[test]
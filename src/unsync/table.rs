@@ -1,20 +1,34 @@
 #![allow(dead_code)]
 
 use crate::Id;
+use crate::Trace;
 use crate::unsync::{Consable, Hc, Weak};
 use std::cell::{Cell, RefCell};
 
 use std::thread::LocalKey as ThreadLocal;
 
+use crate::fxhash::FxBuildHasher;
 use crate::fxhash::FxHashMap as HashMap;
+use crate::fxhash::FxHashSet as HashSet;
 use crate::unsync::TableKey;
+use hashbrown::hash_map::RawEntryMut;
+use std::hash::{BuildHasher, Hash};
 use std::marker::PhantomData;
 
+// The table itself is `hashbrown::HashMap` rather than `fxhash::FxHashMap`
+// (a thin alias over `std::collections::HashMap`) so that `create` can use
+// the raw-entry API below to avoid hashing each key twice.
+type RawTable<T, I> = hashbrown::HashMap<T, Hc<T, I>, FxBuildHasher>;
+
+fn make_hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    FxBuildHasher::default().hash_one(value)
+}
+
 pub struct Table<T: Consable, I: TableKey<T>>(ThreadLocal<InnerTable<T, I>>, PhantomData<I>);
 impl<T: Consable, I: TableKey<T>> Table<T, I> {
     /// # SAFETY
     /// Table<T, I> should be constructed at most once for any concrete type T. As such new_unchecked
-    /// is only intended to be called inside the generate_hashcons macro. The HasTable
+    /// is only intended to be called inside the generate_hashcons macro. The TableKey
     /// implementation ensures that generate_hashcons can only ever be called once per concrete T
     pub const unsafe fn new_unchecked(inner: ThreadLocal<InnerTable<T, I>>) -> Self {
         Table(inner, PhantomData)
@@ -38,12 +52,48 @@ impl<T: Consable, I: TableKey<T>> Table<T, I> {
         })
     }
 
+    /// Run `f` over every interned node using rayon, without first
+    /// collecting the keys into a `Vec` yourself.
+    ///
+    /// The underlying table is thread-local, so this only ever uses the
+    /// calling thread's table; the parallelism comes from handing rayon's
+    /// pool read-only references to process. `T: Sync` is required for that
+    /// sharing to be sound, which also means `f` cannot mutate the table:
+    /// there is no entry point here for inserting or removing nodes.
+    #[cfg(feature = "rayon")]
+    pub fn par_for_each<F>(f: F)
+    where
+        F: Fn(&T) + Sync,
+        T: Sync,
+    {
+        use rayon::prelude::*;
+
+        <I as crate::TableKey>::table().0.with(|inner| {
+            let table = inner.table.borrow();
+            let entries: Vec<&T> = table.values().map(|hc| &**hc).collect();
+            entries.into_par_iter().for_each(|t| f(t));
+        })
+    }
+
     pub(crate) fn create(t: T) -> Hc<T, I> {
         <I as crate::TableKey>::table()
             .0
             .with(|inner| inner.create(t))
     }
 
+    /// Look up an interned value without inserting it.
+    pub fn get(value: &T) -> Option<Hc<T, I>> {
+        <I as crate::TableKey>::table().0.with(|inner| inner.get(value))
+    }
+
+    /// Like [`Hc::new`], but takes the value by reference, only cloning it
+    /// on a miss.
+    pub fn intern_ref(value: &T) -> Hc<T, I> {
+        <I as crate::TableKey>::table()
+            .0
+            .with(|inner| inner.intern_ref(value))
+    }
+
     pub(crate) fn add_to_gc(w: Weak<T, I>) {
         let _ = <I as crate::TableKey>::table().0.try_with(|inner| {
             //inner.gc.borrow_mut().to_collect.push(w);
@@ -62,6 +112,49 @@ impl<T: Consable, I: TableKey<T>> Table<T, I> {
             .with(|inner| inner.table.borrow_mut().reserve(num_nodes))
     }
 
+    /// Bulk-collect everything unreachable from `roots`.
+    ///
+    /// Marks every `Id` reachable from `roots` via [`Trace::children`], then
+    /// sweeps the table in one pass, dropping unmarked entries that are only
+    /// held by the table itself (`strong_count == 1`). An unmarked entry
+    /// still held externally (e.g. stashed somewhere but not passed in as a
+    /// root) is left alone rather than freed out from under its owner.
+    /// Returns the number of entries collected.
+    pub fn gc_from_roots<'a>(roots: impl IntoIterator<Item = &'a Hc<T, I>>) -> usize
+    where
+        T: Trace + 'a,
+    {
+        <I as crate::TableKey>::table()
+            .0
+            .with(|inner| inner.gc_from_roots(roots.into_iter()))
+    }
+
+    /// Serialize the DAG reachable from `roots` to bytes, assigning each
+    /// distinct node a dense index so that equal subterms are emitted once.
+    #[cfg(feature = "archive")]
+    pub fn serialize_dag<'a>(roots: impl IntoIterator<Item = &'a Hc<T, I>>) -> Vec<u8>
+    where
+        T: crate::archive::Dag + 'a,
+    {
+        let roots = roots.into_iter();
+        <I as crate::TableKey>::table()
+            .0
+            .with(|inner| inner.serialize_dag(roots))
+    }
+
+    /// Reconstruct the nodes encoded by [`Table::serialize_dag`], re-interning
+    /// each one into this table as it is built so the result is `==`-equal
+    /// to any already-present structurally-identical terms.
+    #[cfg(feature = "archive")]
+    pub fn deserialize_dag(bytes: &[u8]) -> Vec<Hc<T, I>>
+    where
+        T: crate::archive::Dag<Handle = Hc<T, I>>,
+    {
+        <I as crate::TableKey>::table()
+            .0
+            .with(|inner| inner.deserialize_dag(bytes))
+    }
+
     //pub fn gc_hook_add<I: Into<String>, F: Fn(Id) -> Vec<Hc<T,I>> + 'static>(name: I, f: F) {
     //    <I as crate::TableKey>::table().0.with(|inner| {
     //        let hooks = &mut inner.gc.borrow_mut().hooks;
@@ -123,7 +216,7 @@ impl<T: Consable, I: TableKey<T>> Default for GcData<T, I> {
 }
 
 pub struct InnerTable<T: Consable, I: TableKey<T>> {
-    table: RefCell<HashMap<T, Hc<T, I>>>,
+    table: RefCell<RawTable<T, I>>,
     gc: RefCell<GcData<T, I>>,
 }
 
@@ -142,13 +235,45 @@ impl<T: Consable, I: TableKey<T>> InnerTable<T, I> {
     }
 
     fn create(&self, data: T) -> Hc<T, I> {
-        self.table
-            .borrow_mut()
-            .entry(data)
-            .or_insert_with_key(|key| {
-                Hc::new_unchecked(key.clone())
-            })
-            .clone()
+        let mut table = self.table.borrow_mut();
+        // Compute the content hash once and probe with it directly, instead
+        // of letting `entry` hash `data` and then hash it again on every
+        // subsequent lookup of an already-interned value.
+        let hash = make_hash(&data);
+        match table.raw_entry_mut().from_hash(hash, |k| *k == data) {
+            RawEntryMut::Occupied(entry) => entry.get().clone(),
+            RawEntryMut::Vacant(entry) => {
+                let hc = Hc::new_unchecked(data.clone());
+                let (_, inserted) = entry.insert_with_hasher(hash, data, hc, |k| make_hash(k));
+                inserted.clone()
+            }
+        }
+    }
+
+    /// Look up an interned value without inserting it.
+    fn get(&self, value: &T) -> Option<Hc<T, I>> {
+        let table = self.table.borrow();
+        let hash = make_hash(value);
+        table
+            .raw_entry()
+            .from_hash(hash, |k| k == value)
+            .map(|(_, hc)| hc.clone())
+    }
+
+    /// Like `create`, but takes the value by reference, only cloning it on a
+    /// miss.
+    fn intern_ref(&self, value: &T) -> Hc<T, I> {
+        let mut table = self.table.borrow_mut();
+        let hash = make_hash(value);
+        match table.raw_entry_mut().from_hash(hash, |k| k == value) {
+            RawEntryMut::Occupied(entry) => entry.get().clone(),
+            RawEntryMut::Vacant(entry) => {
+                let data = value.clone();
+                let hc = Hc::new_unchecked(data.clone());
+                let (_, inserted) = entry.insert_with_hasher(hash, data, hc, |k| make_hash(k));
+                inserted.clone()
+            }
+        }
     }
 
     fn gc(&self) -> Option<usize> {
@@ -194,6 +319,98 @@ impl<T: Consable, I: TableKey<T>> InnerTable<T, I> {
         Some(collected)
     }
 
+    fn gc_from_roots<'a>(&self, roots: impl Iterator<Item = &'a Hc<T, I>>) -> usize
+    where
+        T: Trace + 'a,
+    {
+        let mut marked: HashSet<Id> = HashSet::default();
+        let mut worklist: Vec<Id> = roots.map(Hc::id).collect();
+        {
+            // Index the table by Id once up front so the DFS below can look
+            // a child's data back up without rescanning the whole table at
+            // every step.
+            let table = self.table.borrow();
+            let by_id: HashMap<Id, &T> = table.values().map(|hc| (Hc::id(hc), &**hc)).collect();
+            while let Some(id) = worklist.pop() {
+                if !marked.insert(id) {
+                    continue;
+                }
+                if let Some(data) = by_id.get(&id) {
+                    data.children(&mut |child| worklist.push(child));
+                }
+            }
+        }
+
+        let mut table = self.table.borrow_mut();
+        let before = table.len();
+        table.retain(|_, hc| marked.contains(&Hc::id(hc)) || Hc::strong_count(hc) != 1);
+        before - table.len()
+    }
+
+    #[cfg(feature = "archive")]
+    fn serialize_dag<'a>(&self, roots: impl Iterator<Item = &'a Hc<T, I>>) -> Vec<u8>
+    where
+        T: crate::archive::Dag + 'a,
+    {
+        use crate::archive::Frame;
+
+        let table = self.table.borrow();
+        let by_id: HashMap<Id, &T> = table.values().map(|hc| (Hc::id(hc), &**hc)).collect();
+
+        let mut index_of: HashMap<Id, u32> = HashMap::default();
+        let mut nodes: Vec<T::Node> = Vec::new();
+        let mut stack: Vec<Frame> = roots.map(|hc| Frame::Enter(Hc::id(hc))).collect();
+
+        while let Some(frame) = stack.pop() {
+            match frame {
+                Frame::Enter(id) => {
+                    if index_of.contains_key(&id) {
+                        continue;
+                    }
+                    stack.push(Frame::Exit(id));
+                    if let Some(data) = by_id.get(&id) {
+                        data.children(&mut |child| stack.push(Frame::Enter(child)));
+                    }
+                }
+                Frame::Exit(id) => {
+                    if index_of.contains_key(&id) {
+                        continue;
+                    }
+                    let Some(data) = by_id.get(&id) else {
+                        continue;
+                    };
+                    let node = data.to_node(&mut |child| index_of[&child]);
+                    index_of.insert(id, nodes.len() as u32);
+                    nodes.push(node);
+                }
+            }
+        }
+
+        rkyv::to_bytes::<_, 1024>(&nodes)
+            .expect("failed to serialize term DAG")
+            .into_vec()
+    }
+
+    #[cfg(feature = "archive")]
+    fn deserialize_dag(&self, bytes: &[u8]) -> Vec<Hc<T, I>>
+    where
+        T: crate::archive::Dag<Handle = Hc<T, I>>,
+    {
+        // SAFETY: `bytes` must have been produced by `serialize_dag` for
+        // this same `T::Node` layout; rkyv does not validate on its own
+        // without the `bytecheck` feature.
+        let archived = unsafe { rkyv::archived_root::<Vec<T::Node>>(bytes) };
+
+        let mut interned: Vec<Hc<T, I>> = Vec::with_capacity(archived.len());
+        for node in archived.iter() {
+            let mut children: Vec<Hc<T, I>> = Vec::new();
+            T::child_indices(node, &mut |idx| children.push(interned[idx as usize].clone()));
+            let value = T::from_node(node, &children);
+            interned.push(self.create(value));
+        }
+        interned
+    }
+
     fn print_gc_queue(&self) {
         for i in self
             .gc